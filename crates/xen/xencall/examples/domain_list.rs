@@ -0,0 +1,14 @@
+use xencall::error::Result;
+use xencall::XenCall;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+
+    let call = XenCall::open(0)?;
+    let domains = call.get_domain_info_list().await?;
+    for info in domains {
+        println!("{:?}", info);
+    }
+    Ok(())
+}
@@ -0,0 +1,15 @@
+use xencall::error::Result;
+use xencall::XenCall;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+
+    let call = XenCall::open(0)?;
+    let info = call.get_domain_info(1).await?;
+    for vcpu in 0..=info.max_vcpu_id {
+        let vcpu_info = call.get_vcpu_info(1, vcpu).await?;
+        println!("{:?}", vcpu_info);
+    }
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use xencall::error::Result;
+use xencall::XenCall;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+
+    let call = XenCall::open(0)?;
+    let (major, minor) = call.get_version().await?;
+    let extra = call.get_version_extra().await?;
+    let extra = extra
+        .extraversion
+        .iter()
+        .take_while(|c| **c != 0)
+        .map(|c| *c as u8 as char)
+        .collect::<String>();
+    println!("{}.{}{}", major, minor, extra);
+    Ok(())
+}
@@ -18,6 +18,12 @@ pub enum Error {
     MmapBatchFailed(nix::errno::Errno),
     #[error("specified value is too long")]
     ValueTooLong,
+    #[error("vcpu affinity requires a hard or soft cpu set")]
+    VcpuAffinityEmpty,
+    #[error("node affinity requires a node set")]
+    NodeAffinityEmpty,
+    #[error("affinity index {0} is above the host maximum of {1}")]
+    AffinityIndexOutOfRange(u32, u32),
     #[error("failed to join async task: {0}")]
     JoinError(JoinError),
 }
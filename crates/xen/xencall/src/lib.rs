@@ -6,18 +6,21 @@ use crate::sys::{
     AddToPhysmap, AddressSize, AssignDevice, CreateDomain, DomCtl, DomCtlValue, DomCtlVcpuContext,
     EvtChnAllocUnbound, GetDomainInfo, GetPageFrameInfo3, HvmContext, HvmParam, Hypercall,
     HypercallInit, IoMemPermission, IoPortPermission, IrqPermission, MaxMem, MaxVcpus, MemoryMap,
-    MemoryReservation, MmapBatch, MmapResource, MmuExtOp, MultiCallEntry, PagingMempool,
-    PciAssignDevice, XenCapabilitiesInfo, DOMCTL_DEV_PCI, HYPERVISOR_DOMCTL,
-    HYPERVISOR_EVENT_CHANNEL_OP, HYPERVISOR_HVM_OP, HYPERVISOR_MEMORY_OP, HYPERVISOR_MMUEXT_OP,
-    HYPERVISOR_MULTICALL, HYPERVISOR_XEN_VERSION, XENVER_CAPABILITIES, XEN_DOMCTL_ASSIGN_DEVICE,
-    XEN_DOMCTL_CREATEDOMAIN, XEN_DOMCTL_CREATE_DOMAIN2_INTERFACE_THRESHOLD,
-    XEN_DOMCTL_DESTROYDOMAIN, XEN_DOMCTL_GETDOMAININFO, XEN_DOMCTL_GETHVMCONTEXT,
-    XEN_DOMCTL_GETPAGEFRAMEINFO3, XEN_DOMCTL_HYPERCALL_INIT, XEN_DOMCTL_IOMEM_PERMISSION,
+    MemoryReservation, MmapBatch, MmapResource, MmuExtOp, MultiCallEntry, NodeAffinity,
+    PagingMempool, PciAssignDevice, VcpuAffinity, VcpuInfo, XenCapabilitiesInfo, XenCtlBitmap,
+    XenExtraVersionInfo, DOMCTL_DEV_PCI, HYPERVISOR_DOMCTL, HYPERVISOR_EVENT_CHANNEL_OP,
+    HYPERVISOR_HVM_OP, HYPERVISOR_MEMORY_OP, HYPERVISOR_MMUEXT_OP, HYPERVISOR_MULTICALL,
+    HYPERVISOR_XEN_VERSION, XENVER_CAPABILITIES, XENVER_EXTRAVERSION, XENVER_VERSION,
+    XEN_DOMCTL_ASSIGN_DEVICE, XEN_DOMCTL_CREATEDOMAIN,
+    XEN_DOMCTL_CREATE_DOMAIN2_INTERFACE_THRESHOLD, XEN_DOMCTL_DESTROYDOMAIN,
+    XEN_DOMCTL_GETDOMAININFO, XEN_DOMCTL_GETHVMCONTEXT, XEN_DOMCTL_GETPAGEFRAMEINFO3,
+    XEN_DOMCTL_GETVCPUINFO, XEN_DOMCTL_HYPERCALL_INIT, XEN_DOMCTL_IOMEM_PERMISSION,
     XEN_DOMCTL_IOPORT_PERMISSION, XEN_DOMCTL_IRQ_PERMISSION, XEN_DOMCTL_MAX_MEM,
     XEN_DOMCTL_MAX_VCPUS, XEN_DOMCTL_PAUSEDOMAIN, XEN_DOMCTL_SETHVMCONTEXT,
-    XEN_DOMCTL_SETVCPUCONTEXT, XEN_DOMCTL_SET_ADDRESS_SIZE, XEN_DOMCTL_SET_PAGING_MEMPOOL_SIZE,
-    XEN_DOMCTL_UNPAUSEDOMAIN, XEN_MEM_ADD_TO_PHYSMAP, XEN_MEM_CLAIM_PAGES, XEN_MEM_MEMORY_MAP,
-    XEN_MEM_POPULATE_PHYSMAP,
+    XEN_DOMCTL_SETNODEAFFINITY, XEN_DOMCTL_SETVCPUAFFINITY, XEN_DOMCTL_SETVCPUCONTEXT,
+    XEN_DOMCTL_SET_ADDRESS_SIZE, XEN_DOMCTL_SET_PAGING_MEMPOOL_SIZE, XEN_DOMCTL_UNPAUSEDOMAIN,
+    XEN_MEM_ADD_TO_PHYSMAP, XEN_MEM_CLAIM_PAGES, XEN_MEM_MEMORY_MAP, XEN_MEM_POPULATE_PHYSMAP,
+    XEN_VCPUAFFINITY_HARD, XEN_VCPUAFFINITY_SOFT,
 };
 use libc::{c_int, mmap, MAP_FAILED, MAP_SHARED, PROT_READ, PROT_WRITE};
 use log::trace;
@@ -27,13 +30,14 @@ use std::sync::Arc;
 use std::time::Duration;
 use sys::{
     CpuId, E820Entry, ForeignMemoryMap, PhysdevMapPirq, SetDomainHandle, Sysctl, SysctlCputopo,
-    SysctlCputopoinfo, SysctlPhysinfo, SysctlPmOp, SysctlPmOpValue, SysctlReadconsole,
-    SysctlSetCpuFreqGov, SysctlValue, VcpuGuestContextAny, HYPERVISOR_PHYSDEV_OP,
-    HYPERVISOR_SYSCTL, PHYSDEVOP_MAP_PIRQ, XEN_DOMCTL_MAX_INTERFACE_VERSION,
+    SysctlCputopoinfo, SysctlGetDomainInfoList, SysctlPhysinfo, SysctlPmOp, SysctlPmOpValue,
+    SysctlReadconsole, SysctlSetCpuFreqGov, SysctlValue, VcpuGuestContextAny,
+    HYPERVISOR_PHYSDEV_OP, HYPERVISOR_SYSCTL, PHYSDEVOP_MAP_PIRQ, XEN_DOMCTL_MAX_INTERFACE_VERSION,
     XEN_DOMCTL_MIN_INTERFACE_VERSION, XEN_DOMCTL_SETDOMAINHANDLE, XEN_MEM_SET_MEMORY_MAP,
-    XEN_SYSCTL_CPUTOPOINFO, XEN_SYSCTL_MAX_INTERFACE_VERSION, XEN_SYSCTL_MIN_INTERFACE_VERSION,
-    XEN_SYSCTL_PHYSINFO, XEN_SYSCTL_PM_OP, XEN_SYSCTL_PM_OP_DISABLE_TURBO,
-    XEN_SYSCTL_PM_OP_ENABLE_TURBO, XEN_SYSCTL_PM_OP_SET_CPUFREQ_GOV, XEN_SYSCTL_READCONSOLE,
+    XEN_SYSCTL_CPUTOPOINFO, XEN_SYSCTL_GETDOMAININFOLIST, XEN_SYSCTL_MAX_INTERFACE_VERSION,
+    XEN_SYSCTL_MIN_INTERFACE_VERSION, XEN_SYSCTL_PHYSINFO, XEN_SYSCTL_PM_OP,
+    XEN_SYSCTL_PM_OP_DISABLE_TURBO, XEN_SYSCTL_PM_OP_ENABLE_TURBO,
+    XEN_SYSCTL_PM_OP_SET_CPUFREQ_GOV, XEN_SYSCTL_READCONSOLE,
 };
 use tokio::time::sleep;
 
@@ -42,6 +46,8 @@ use std::os::fd::AsRawFd;
 use std::ptr::{addr_of_mut, null_mut};
 use std::slice;
 
+const DOMAIN_INFO_LIST_BATCH_SIZE: usize = 64;
+
 #[derive(Clone)]
 pub struct XenCall {
     pub handle: Arc<File>,
@@ -342,6 +348,28 @@ impl XenCall {
         }
     }
 
+    pub async fn get_version(&self) -> Result<(u32, u32)> {
+        trace!("call fd={} get_version", self.handle.as_raw_fd());
+        let version = self
+            .hypercall2(HYPERVISOR_XEN_VERSION, XENVER_VERSION, 0)
+            .await? as u32;
+        Ok((version >> 16, version & 0xffff))
+    }
+
+    pub async fn get_version_extra(&self) -> Result<XenExtraVersionInfo> {
+        trace!("call fd={} get_version_extra", self.handle.as_raw_fd());
+        let mut info = XenExtraVersionInfo {
+            extraversion: [0; 16],
+        };
+        self.hypercall2(
+            HYPERVISOR_XEN_VERSION,
+            XENVER_EXTRAVERSION,
+            addr_of_mut!(info) as c_ulong,
+        )
+        .await?;
+        Ok(info)
+    }
+
     pub async fn get_version_capabilities(&self) -> Result<XenCapabilitiesInfo> {
         trace!(
             "call fd={} get_version_capabilities",
@@ -441,6 +469,29 @@ impl XenCall {
         Ok(domctl.domid)
     }
 
+    pub async fn get_vcpu_info(&self, domid: u32, vcpu: u32) -> Result<VcpuInfo> {
+        trace!(
+            "domctl fd={} get_vcpu_info domid={} vcpu={}",
+            self.handle.as_raw_fd(),
+            domid,
+            vcpu
+        );
+        let mut domctl = DomCtl {
+            cmd: XEN_DOMCTL_GETVCPUINFO,
+            interface_version: self.domctl_interface_version,
+            domid,
+            value: DomCtlValue {
+                vcpu_info: VcpuInfo {
+                    vcpu,
+                    ..Default::default()
+                },
+            },
+        };
+        self.hypercall1(HYPERVISOR_DOMCTL, addr_of_mut!(domctl) as c_ulong)
+            .await?;
+        Ok(unsafe { domctl.value.vcpu_info })
+    }
+
     pub async fn pause_domain(&self, domid: u32) -> Result<()> {
         trace!(
             "domctl fd={} pause_domain domid={:?}",
@@ -515,6 +566,105 @@ impl XenCall {
         Ok(())
     }
 
+    pub async fn set_vcpu_affinity(
+        &self,
+        domid: u32,
+        vcpu: u32,
+        hard: &[u32],
+        soft: &[u32],
+    ) -> Result<()> {
+        trace!(
+            "domctl fd={} set_vcpu_affinity domid={} vcpu={} hard={:?} soft={:?}",
+            self.handle.as_raw_fd(),
+            domid,
+            vcpu,
+            hard,
+            soft
+        );
+        if hard.is_empty() && soft.is_empty() {
+            return Err(Error::VcpuAffinityEmpty);
+        }
+        let max_cpu_id = self.phys_info().await?.max_cpu_id;
+        let mut hard_bitmap = XenCall::indexes_to_bitmap(hard, max_cpu_id)?;
+        let mut soft_bitmap = XenCall::indexes_to_bitmap(soft, max_cpu_id)?;
+        let mut flags = 0;
+        if !hard.is_empty() {
+            flags |= XEN_VCPUAFFINITY_HARD;
+        }
+        if !soft.is_empty() {
+            flags |= XEN_VCPUAFFINITY_SOFT;
+        }
+        let mut domctl = DomCtl {
+            cmd: XEN_DOMCTL_SETVCPUAFFINITY,
+            interface_version: self.domctl_interface_version,
+            domid,
+            value: DomCtlValue {
+                vcpu_affinity: VcpuAffinity {
+                    vcpu,
+                    flags,
+                    cpumap_hard: XenCtlBitmap {
+                        bitmap: hard_bitmap.as_mut_ptr() as u64,
+                        nr_bits: (hard_bitmap.len() * 8) as u32,
+                    },
+                    cpumap_soft: XenCtlBitmap {
+                        bitmap: soft_bitmap.as_mut_ptr() as u64,
+                        nr_bits: (soft_bitmap.len() * 8) as u32,
+                    },
+                },
+            },
+        };
+        self.hypercall1(HYPERVISOR_DOMCTL, addr_of_mut!(domctl) as c_ulong)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_node_affinity(&self, domid: u32, nodes: &[u32]) -> Result<()> {
+        trace!(
+            "domctl fd={} set_node_affinity domid={} nodes={:?}",
+            self.handle.as_raw_fd(),
+            domid,
+            nodes
+        );
+        if nodes.is_empty() {
+            return Err(Error::NodeAffinityEmpty);
+        }
+        let max_node_id = self.phys_info().await?.max_node_id;
+        let mut bitmap = XenCall::indexes_to_bitmap(nodes, max_node_id)?;
+        let mut domctl = DomCtl {
+            cmd: XEN_DOMCTL_SETNODEAFFINITY,
+            interface_version: self.domctl_interface_version,
+            domid,
+            value: DomCtlValue {
+                node_affinity: NodeAffinity {
+                    nodemap: XenCtlBitmap {
+                        bitmap: bitmap.as_mut_ptr() as u64,
+                        nr_bits: (bitmap.len() * 8) as u32,
+                    },
+                },
+            },
+        };
+        self.hypercall1(HYPERVISOR_DOMCTL, addr_of_mut!(domctl) as c_ulong)
+            .await?;
+        Ok(())
+    }
+
+    fn indexes_to_bitmap(indexes: &[u32], max_id: u32) -> Result<Vec<u8>> {
+        let Some(max) = indexes.iter().max() else {
+            return Ok(Vec::new());
+        };
+        if *max > max_id {
+            return Err(Error::AffinityIndexOutOfRange(*max, max_id));
+        }
+        let bits = max
+            .checked_add(1)
+            .ok_or(Error::AffinityIndexOutOfRange(*max, max_id))? as usize;
+        let mut bitmap = vec![0u8; bits.div_ceil(8)];
+        for index in indexes {
+            bitmap[*index as usize / 8] |= 1 << (index % 8);
+        }
+        Ok(bitmap)
+    }
+
     pub async fn set_address_size(&self, domid: u32, size: u32) -> Result<()> {
         trace!(
             "domctl fd={} set_address_size domid={} size={}",
@@ -1045,6 +1195,40 @@ impl XenCall {
         Ok(unsafe { sysctl.value.phys_info })
     }
 
+    pub async fn get_domain_info_list(&self) -> Result<Vec<GetDomainInfo>> {
+        trace!("sysctl fd={} get_domain_info_list", self.handle.as_raw_fd());
+        let mut domains = Vec::new();
+        let mut first_domain: u16 = 0;
+        loop {
+            let mut buffer = vec![GetDomainInfo::default(); DOMAIN_INFO_LIST_BATCH_SIZE];
+            let mut sysctl = Sysctl {
+                cmd: XEN_SYSCTL_GETDOMAININFOLIST,
+                interface_version: self.sysctl_interface_version,
+                value: SysctlValue {
+                    get_domain_info_list: SysctlGetDomainInfoList {
+                        first_domain,
+                        max_domains: buffer.len() as u32,
+                        buffer: buffer.as_mut_ptr() as c_ulong,
+                        num_domains: 0,
+                    },
+                },
+            };
+            self.hypercall1(HYPERVISOR_SYSCTL, addr_of_mut!(sysctl) as c_ulong)
+                .await?;
+            let count = unsafe { sysctl.value.get_domain_info_list.num_domains } as usize;
+            buffer.truncate(count);
+            let Some(last) = buffer.last() else {
+                break;
+            };
+            first_domain = last.domid.wrapping_add(1);
+            domains.extend(buffer);
+            if count < DOMAIN_INFO_LIST_BATCH_SIZE || first_domain == 0 {
+                break;
+            }
+        }
+        Ok(domains)
+    }
+
     pub async fn set_cpufreq_gov(&self, cpuid: CpuId, gov: impl AsRef<str>) -> Result<()> {
         match cpuid {
             CpuId::All => {
@@ -256,6 +256,9 @@ pub union DomCtlValue {
     pub hvm_context: HvmContext,
     pub paging_mempool: PagingMempool,
     pub set_domain_handle: SetDomainHandle,
+    pub vcpu_affinity: VcpuAffinity,
+    pub node_affinity: NodeAffinity,
+    pub vcpu_info: VcpuInfo,
     pub pad: [u8; 128],
 }
 
@@ -339,6 +342,42 @@ pub struct SetDomainHandle {
     pub handle: [u8; 16],
 }
 
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct XenCtlBitmap {
+    pub bitmap: u64,
+    pub nr_bits: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct VcpuAffinity {
+    pub vcpu: u32,
+    pub flags: u32,
+    pub cpumap_hard: XenCtlBitmap,
+    pub cpumap_soft: XenCtlBitmap,
+}
+
+pub const XEN_VCPUAFFINITY_HARD: u32 = 1u32 << 0;
+pub const XEN_VCPUAFFINITY_SOFT: u32 = 1u32 << 1;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct VcpuInfo {
+    pub vcpu: u32,
+    pub online: u8,
+    pub blocked: u8,
+    pub running: u8,
+    pub cpu_time: u64,
+    pub cpu: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NodeAffinity {
+    pub nodemap: XenCtlBitmap,
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Default)]
 pub struct GetDomainInfo {
@@ -444,6 +483,14 @@ pub struct XenCapabilitiesInfo {
     pub capabilities: [c_char; 1024],
 }
 
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct XenExtraVersionInfo {
+    pub extraversion: [c_char; 16],
+}
+
+pub const XENVER_VERSION: u64 = 0;
+pub const XENVER_EXTRAVERSION: u64 = 1;
 pub const XENVER_CAPABILITIES: u64 = 3;
 
 #[repr(C)]
@@ -807,12 +854,22 @@ pub struct SysctlCputopoinfo {
     pub handle: c_ulong,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SysctlGetDomainInfoList {
+    pub first_domain: u16,
+    pub max_domains: u32,
+    pub buffer: c_ulong,
+    pub num_domains: u32,
+}
+
 #[repr(C)]
 pub union SysctlValue {
     pub console: SysctlReadconsole,
     pub cputopoinfo: SysctlCputopoinfo,
     pub pm_op: SysctlPmOp,
     pub phys_info: SysctlPhysinfo,
+    pub get_domain_info_list: SysctlGetDomainInfoList,
     pub pad: [u8; 128],
 }
 
@@ -825,6 +882,7 @@ pub struct Sysctl {
 
 pub const XEN_SYSCTL_READCONSOLE: u32 = 1;
 pub const XEN_SYSCTL_PHYSINFO: u32 = 3;
+pub const XEN_SYSCTL_GETDOMAININFOLIST: u32 = 6;
 pub const XEN_SYSCTL_PM_OP: u32 = 12;
 pub const XEN_SYSCTL_CPUTOPOINFO: u32 = 16;
 
@@ -835,6 +893,8 @@ pub const XEN_SYSCTL_PM_OP_SET_SCHED_OPT_STMT: u32 = 0x21;
 pub const XEN_SYSCTL_PM_OP_ENABLE_TURBO: u32 = 0x26;
 pub const XEN_SYSCTL_PM_OP_DISABLE_TURBO: u32 = 0x27;
 
+pub const XEN_SYSCTL_PHYSCAP_DIRECTIO: u32 = 1u32 << 2;
+
 #[derive(Clone, Copy, Debug)]
 pub enum CpuId {
     All,
@@ -1,9 +1,11 @@
+use std::str::FromStr;
 use std::sync::Arc;
 use std::{env, process};
 use tokio::fs;
+use tokio::task::JoinSet;
 use uuid::Uuid;
 use xenclient::config::{DomainConfig, DomainResult};
-use xenclient::error::Result;
+use xenclient::error::{Error, Result};
 use xenclient::tx::channel::ChannelDeviceConfig;
 use xenclient::XenClient;
 use xenplatform::domain::{
@@ -18,11 +20,16 @@ async fn main() -> Result<()> {
     env_logger::init();
 
     let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        println!("usage: boot-speed <kernel-image>");
+    if args.len() != 2 && args.len() != 3 {
+        println!("usage: boot-speed <kernel-image> [parallel-launches]");
         process::exit(1);
     }
     let kernel_path = args.get(1).expect("argument not specified");
+    let parallel = args
+        .get(2)
+        .map(|count| u32::from_str(count))
+        .transpose()?
+        .unwrap_or(0);
     let kernel = Arc::new(fs::read(kernel_path).await?);
     let kernel = ElfImageLoader::load(kernel)?.into_elf_bytes();
     let client = XenClient::new().await?;
@@ -35,6 +42,41 @@ async fn main() -> Result<()> {
         println!("boot setup time: {:?}", duration);
         client.destroy(domain.platform.domid).await?;
     }
+
+    if parallel > 0 {
+        let start = std::time::Instant::now();
+        let mut tasks = JoinSet::new();
+        for i in 0..parallel {
+            let client = client.clone();
+            let kernel = kernel.clone();
+            tasks.spawn(async move { create_domain(&client, kernel, 100 + i).await });
+        }
+        let mut domains = Vec::new();
+        let mut failure = None;
+        while let Some(result) = tasks.join_next().await {
+            match result.map_err(|error| Error::GenericError(error.to_string())) {
+                Ok(Ok(domain)) => domains.push(domain),
+                Ok(Err(error)) | Err(error) => {
+                    failure.get_or_insert(error);
+                }
+            }
+        }
+        let duration = std::time::Instant::now() - start;
+        for domain in &domains {
+            if let Err(error) = client.destroy(domain.platform.domid).await {
+                failure.get_or_insert(error);
+            }
+        }
+        if let Some(error) = failure {
+            return Err(error);
+        }
+        println!(
+            "parallel boot setup time: {:?} for {} domains ({:.2} launches/second)",
+            duration,
+            parallel,
+            parallel as f64 / duration.as_secs_f64()
+        );
+    }
     Ok(())
 }
 
@@ -42,12 +42,24 @@ pub enum Error {
     InvalidPciBdfString,
     #[error("pci device {0} is not assignable")]
     PciDeviceNotAssignable(PciBdf),
+    #[error("iommu is not available, pci passthrough is not possible")]
+    IommuUnavailable,
     #[error("xen platform error: {0}")]
     XenPlatform(#[from] xenplatform::error::Error),
     #[error("invalid block index")]
     InvalidBlockIdx,
     #[error("device state wait error: {0}")]
     DevStateWaitError(String),
+    #[error("memory target {0}KiB exceeds static maximum {1}KiB")]
+    MemoryTargetExceedsMax(u64, u64),
+    #[error("memory target {0}KiB is below the minimum of {1}KiB")]
+    MemoryTargetBelowMin(u64, u64),
+    #[error("vcpu count {0} is outside of the allowed range 1..={1}")]
+    VcpuCountOutOfRange(u32, u32),
+    #[error("{0} is not a block device")]
+    NotBlockDevice(String),
+    #[error("block device {0} is already in use")]
+    BlockDeviceInUse(String),
     #[error("device ids exhausted")]
     DevIdExhausted,
 }
@@ -2,15 +2,20 @@ pub mod error;
 
 use config::{DomainConfig, DomainResult};
 use error::{Error, Result};
-use log::{debug, trace};
-use tokio::time::timeout;
-use tx::{DeviceConfig, XenTransaction};
+use log::{debug, trace, warn};
+use nix::errno::Errno;
+use tokio::time::{sleep, timeout, Instant};
+use tx::{
+    vbd::VbdDeviceConfig, vif::VifDeviceConfig, BlockDeviceResult, DeviceConfig, DeviceResult,
+    XenTransaction,
+};
 use xenplatform::domain::{PlatformDomainInfo, PlatformDomainManager};
 
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
+use xencall::sys::{GetDomainInfo, XEN_DOMINF_DYING, XEN_DOMINF_PAUSED, XEN_DOMINF_SHUTDOWN};
 use xencall::XenCall;
 use xenstore::{XsdClient, XsdInterface};
 
@@ -21,6 +26,23 @@ pub mod pci;
 pub mod tx;
 pub mod util;
 
+const MEMORY_TARGET_MIN_MB: u64 = 32;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ShutdownRequest {
+    Poweroff,
+    Reboot,
+}
+
+impl ShutdownRequest {
+    pub fn to_option_str(&self) -> &str {
+        match self {
+            ShutdownRequest::Poweroff => "poweroff",
+            ShutdownRequest::Reboot => "reboot",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct XenClient {
     pub store: XsdClient,
@@ -154,6 +176,272 @@ impl XenClient {
         })
     }
 
+    pub async fn pause(&self, domid: u32) -> Result<()> {
+        self.call.pause_domain(domid).await?;
+        Ok(())
+    }
+
+    pub async fn resume(&self, domid: u32) -> Result<()> {
+        self.call.unpause_domain(domid).await?;
+        Ok(())
+    }
+
+    pub async fn get_domain_info(&self, domid: u32) -> Result<GetDomainInfo> {
+        let info = match self.call.get_domain_info(domid).await {
+            Ok(info) => info,
+            Err(xencall::error::Error::Kernel(Errno::ESRCH)) => {
+                return Err(Error::DomainNonExistent)
+            }
+            Err(error) => return Err(error.into()),
+        };
+        // older domctl interfaces return the next domain when the requested one is missing
+        if info.domid as u32 != domid {
+            return Err(Error::DomainNonExistent);
+        }
+        Ok(info)
+    }
+
+    pub async fn is_paused(&self, domid: u32) -> Result<bool> {
+        let info = self.get_domain_info(domid).await?;
+        Ok((info.flags & XEN_DOMINF_PAUSED) != 0)
+    }
+
+    pub async fn request_shutdown(&self, domid: u32, request: ShutdownRequest) -> Result<()> {
+        let dom_path = self.store.get_domain_path(domid).await?;
+        self.store
+            .write_string(
+                format!("{}/control/shutdown", dom_path),
+                request.to_option_str(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Requests a PV shutdown and waits up to `grace` for the guest to act on it, then destroys
+    /// the domain either way. A reboot request therefore only ends the current domain, relaunching
+    /// it is up to the caller. Returns whether the guest shut down on its own.
+    pub async fn shutdown(
+        &self,
+        domid: u32,
+        request: ShutdownRequest,
+        grace: Duration,
+    ) -> Result<bool> {
+        let mut watch = self.store.create_watch("@releaseDomain").await?;
+        self.store.bind_watch(&watch).await?;
+        self.request_shutdown(domid, request).await?;
+        let deadline = Instant::now() + grace;
+        let graceful = loop {
+            match self.get_domain_info(domid).await {
+                Ok(info) => {
+                    if (info.flags & (XEN_DOMINF_SHUTDOWN | XEN_DOMINF_DYING)) != 0 {
+                        break true;
+                    }
+                }
+                Err(Error::DomainNonExistent) => {
+                    let _ = self.destroy_store(domid).await;
+                    return Ok(true);
+                }
+                Err(error) => return Err(error),
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                debug!(
+                    "domain {} did not shutdown within {:?}, destroying",
+                    domid, grace
+                );
+                break false;
+            }
+            if let Ok(None) = timeout(remaining, watch.receiver.recv()).await {
+                sleep(remaining.min(Duration::from_millis(250))).await;
+            }
+        };
+        self.destroy(domid).await?;
+        Ok(graceful)
+    }
+
+    pub async fn get_memory_target(&self, domid: u32) -> Result<u64> {
+        let dom_path = self.store.get_domain_path(domid).await?;
+        let target_kb = self
+            .store
+            .read_string(format!("{}/memory/target", dom_path))
+            .await?
+            .ok_or(Error::DomainNonExistent)?;
+        Ok(u64::from_str(&target_kb)? / 1024)
+    }
+
+    pub async fn set_memory_target(&self, domid: u32, target_mb: u64) -> Result<()> {
+        let dom_path = self.store.get_domain_path(domid).await?;
+        let static_max_kb = self
+            .store
+            .read_string(format!("{}/memory/static-max", dom_path))
+            .await?
+            .ok_or(Error::DomainNonExistent)?;
+        let static_max_kb = u64::from_str(&static_max_kb)?;
+        let target_kb = target_mb
+            .checked_mul(1024)
+            .ok_or(Error::MemoryTargetExceedsMax(u64::MAX, static_max_kb))?;
+        if target_mb < MEMORY_TARGET_MIN_MB {
+            return Err(Error::MemoryTargetBelowMin(
+                target_kb,
+                MEMORY_TARGET_MIN_MB * 1024,
+            ));
+        }
+        if target_kb > static_max_kb {
+            return Err(Error::MemoryTargetExceedsMax(target_kb, static_max_kb));
+        }
+        self.store
+            .write_string(
+                format!("{}/memory/target", dom_path),
+                &target_kb.to_string(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_online_vcpus(&self, domid: u32, count: u32) -> Result<()> {
+        let dom_path = self.store.get_domain_path(domid).await?;
+        let max_vcpus = self
+            .store
+            .list(format!("{}/cpu", dom_path).as_str())
+            .await?
+            .len() as u32;
+        if count == 0 || count > max_vcpus {
+            return Err(Error::VcpuCountOutOfRange(count, max_vcpus));
+        }
+        loop {
+            let tx = self.store.transaction().await?;
+            for i in 0..max_vcpus {
+                tx.write_string(
+                    format!("{}/cpu/{}/availability", dom_path, i),
+                    if i < count { "online" } else { "offline" },
+                )
+                .await?;
+            }
+            if tx.maybe_commit().await? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn attach_vbd(
+        &self,
+        domid: u32,
+        backend_domid: u32,
+        vbd: &VbdDeviceConfig,
+    ) -> Result<BlockDeviceResult> {
+        self.attach_device(domid, backend_domid, vbd).await
+    }
+
+    pub async fn attach_vif(
+        &self,
+        domid: u32,
+        backend_domid: u32,
+        vif: &VifDeviceConfig,
+    ) -> Result<DeviceResult> {
+        self.attach_device(domid, backend_domid, vif).await
+    }
+
+    async fn attach_device<D: DeviceConfig + Sync>(
+        &self,
+        domid: u32,
+        backend_domid: u32,
+        device: &D,
+    ) -> Result<D::Result> {
+        loop {
+            let tx = self.transaction(domid, backend_domid).await?;
+            let result = device.add_to_transaction(&tx).await?;
+            if tx.maybe_commit().await? {
+                return Ok(result);
+            }
+        }
+    }
+
+    pub async fn detach_vbd(&self, domid: u32, vbd: &BlockDeviceResult) -> Result<()> {
+        self.destroy_device("vbd", domid, vbd.id, Some(vbd.idx))
+            .await
+    }
+
+    pub async fn detach_vif(&self, domid: u32, vif: &DeviceResult) -> Result<()> {
+        self.destroy_device("vif", domid, vif.id, None).await
+    }
+
+    pub async fn get_console_path(&self, domid: u32) -> Result<String> {
+        let dom_path = self.store.get_domain_path(domid).await?;
+        let console_tty_path = format!("{}/console/tty", dom_path);
+        let tty = self
+            .store
+            .read_string(&console_tty_path)
+            .await?
+            .ok_or(Error::TtyNotFound)?;
+        if tty.is_empty() {
+            return Err(Error::TtyNotFound);
+        }
+        Ok(tty)
+    }
+
+    pub async fn cleanup_stale_domains(&self, backend_domid: u32) -> Result<Vec<u32>> {
+        // snapshot xenstore before querying xen, so domains created in between are never touched.
+        // domains that exist in xen without a vm declaration are left alone: they cannot be told
+        // apart from domains that another client is still building.
+        let domain_paths = self.store.list("/local/domain").await?;
+        let backend_path = format!(
+            "{}/backend",
+            self.store.get_domain_path(backend_domid).await?
+        );
+        let mut backend_paths = Vec::new();
+        for backend_type in self.store.list(&backend_path).await? {
+            let backend_type_path = format!("{}/{}", backend_path, backend_type);
+            for domid in self.store.list(&backend_type_path).await? {
+                backend_paths.push((domid.clone(), format!("{}/{}", backend_type_path, domid)));
+            }
+        }
+
+        let live = self
+            .call
+            .get_domain_info_list()
+            .await?
+            .into_iter()
+            .map(|info| info.domid as u32)
+            .collect::<Vec<_>>();
+
+        let mut stale = Vec::new();
+        for domid in domain_paths {
+            let Ok(domid) = u32::from_str(&domid) else {
+                continue;
+            };
+            if domid == 0 || live.contains(&domid) {
+                continue;
+            }
+            debug!("cleaning up stale xenstore entries for domain {}", domid);
+            if let Err(error) = self.destroy_store(domid).await {
+                debug!("failed to destroy store for domain {}: {}", domid, error);
+                if let Err(error) = self.store.rm(&format!("/local/domain/{}", domid)).await {
+                    warn!("failed to remove stale domain {}: {}", domid, error);
+                    continue;
+                }
+            }
+            stale.push(domid);
+        }
+
+        for (domid, path) in backend_paths {
+            let Ok(domid) = u32::from_str(&domid) else {
+                continue;
+            };
+            if live.contains(&domid) {
+                continue;
+            }
+            if let Err(error) = self.store.rm(&path).await {
+                warn!("failed to remove stale backend {}: {}", path, error);
+                continue;
+            }
+            if !stale.contains(&domid) {
+                stale.push(domid);
+            }
+        }
+        Ok(stale)
+    }
+
     pub async fn destroy(&self, domid: u32) -> Result<()> {
         let _ = self.destroy_store(domid).await;
         self.domain_manager.destroy(domid).await?;
@@ -200,23 +488,27 @@ impl XenClient {
             self.destroy_backend(backend).await?;
         }
 
-        let tx = self.store.transaction().await?;
         let mut backend_removals: Vec<String> = Vec::new();
         backend_removals.extend_from_slice(backend_paths.as_slice());
         if let Some(backend) = console_backend_path {
             backend_removals.push(backend);
         }
-        for path in &backend_removals {
-            let path = PathBuf::from(path);
-            let parent = path.parent().ok_or(Error::PathParentNotFound)?;
-            tx.rm(parent.to_str().ok_or(Error::PathStringConversion)?)
-                .await?;
-        }
-        if let Some(vm_path) = vm_path {
-            tx.rm(&vm_path).await?;
+        loop {
+            let tx = self.store.transaction().await?;
+            for path in &backend_removals {
+                let path = PathBuf::from(path);
+                let parent = path.parent().ok_or(Error::PathParentNotFound)?;
+                tx.rm(parent.to_str().ok_or(Error::PathStringConversion)?)
+                    .await?;
+            }
+            if let Some(vm_path) = vm_path.as_ref() {
+                tx.rm(vm_path).await?;
+            }
+            tx.rm(&dom_path).await?;
+            if tx.maybe_commit().await? {
+                break;
+            }
         }
-        tx.rm(&dom_path).await?;
-        tx.commit().await?;
         Ok(())
     }
 
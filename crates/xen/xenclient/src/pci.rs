@@ -5,6 +5,7 @@ use tokio::fs;
 use crate::error::{Error, Result};
 
 const PCIBACK_SYSFS_PATH: &str = "/sys/bus/pci/drivers/pciback";
+const PCI_DEVICES_SYSFS_PATH: &str = "/sys/bus/pci/devices";
 const PCI_BDF_REGEX: &str = r"^([0-9a-f]{4}):([0-9a-f]{2}):([0-9a-f]{2}).([0-9a-f]{1})$";
 const PCI_BDF_SHORT_REGEX: &str = r"^([0-9a-f]{2}):([0-9a-f]{2}).([0-9a-f]{1})$";
 const PCI_BDF_VDEFN_REGEX: &str =
@@ -46,6 +47,12 @@ impl XenPciBackend {
         Ok(devices)
     }
 
+    pub async fn is_present(&self, bdf: &PciBdf) -> Result<bool> {
+        let mut path = PathBuf::from(PCI_DEVICES_SYSFS_PATH);
+        path.push(bdf.to_string());
+        Ok(fs::try_exists(path).await?)
+    }
+
     pub async fn is_assigned(&self, bdf: &PciBdf) -> Result<bool> {
         let mut path = self.path.clone();
         path.push(bdf.to_string());
@@ -133,6 +140,32 @@ impl XenPciBackend {
         Ok(false)
     }
 
+    pub async fn make_assignable(&self, bdf: &PciBdf) -> Result<()> {
+        let bdf = &bdf.normalize();
+        if self.is_assigned(bdf).await? {
+            return Ok(());
+        }
+
+        let mut unbind_path = PathBuf::from(PCI_DEVICES_SYSFS_PATH);
+        unbind_path.push(bdf.to_string());
+        unbind_path.push("driver");
+        unbind_path.push("unbind");
+        if fs::try_exists(&unbind_path).await? {
+            fs::write(&unbind_path, bdf.to_string()).await?;
+        }
+
+        if !self.has_slot(bdf).await? {
+            let mut new_slot_path = self.path.clone();
+            new_slot_path.push("new_slot");
+            fs::write(new_slot_path, bdf.to_string()).await?;
+        }
+
+        let mut bind_path = self.path.clone();
+        bind_path.push("bind");
+        fs::write(bind_path, bdf.to_string()).await?;
+        Ok(())
+    }
+
     pub async fn reset(&self, bdf: &PciBdf) -> Result<()> {
         let mut path: PathBuf = self.path.clone();
         path.push(bdf.to_string());
@@ -178,6 +211,14 @@ impl PciBdf {
         }
     }
 
+    pub fn normalize(&self) -> PciBdf {
+        PciBdf {
+            domain: Some(self.domain.unwrap_or(0)),
+            vdefn: None,
+            ..*self
+        }
+    }
+
     pub fn encode(&self) -> u32 {
         let mut value = self.domain.unwrap_or(0) << 16u32;
         value |= ((self.bus & 0xff) << 8u32) as u32;
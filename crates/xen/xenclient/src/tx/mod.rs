@@ -8,7 +8,12 @@ use crate::{
     devalloc::DeviceIdAllocator,
     error::{Error, Result},
 };
-use std::{collections::HashMap, sync::Arc};
+use nix::sys::stat::{major, minor};
+use std::{
+    collections::HashMap,
+    os::unix::fs::{FileTypeExt, MetadataExt},
+    sync::Arc,
+};
 use tokio::sync::Mutex;
 use xenplatform::domain::{PlatformDomainConfig, PlatformDomainInfo};
 use xenstore::{
@@ -92,6 +97,38 @@ impl XenTransaction {
         Ok(())
     }
 
+    pub async fn is_block_device_in_use(
+        &self,
+        block_device: &BlockDeviceRef,
+        writable: bool,
+    ) -> Result<bool> {
+        let vbd_backend_path = format!("{}/backend/vbd", self.backend_dom_path);
+        for domid in self.tx.list(&vbd_backend_path).await? {
+            let domain_path = format!("{}/{}", vbd_backend_path, domid);
+            for devid in self.tx.list(&domain_path).await? {
+                let device_path = format!("{}/{}", domain_path, devid);
+                let Some(existing) = self
+                    .tx
+                    .read_string(format!("{}/physical-device", device_path))
+                    .await?
+                else {
+                    continue;
+                };
+                if BlockDeviceRef::parse_physical_device(&existing)
+                    != Some((block_device.major, block_device.minor))
+                {
+                    continue;
+                }
+                // read-only sharing is allowed, like the check_sharing hotplug script.
+                let mode = self.tx.read_string(format!("{}/mode", device_path)).await?;
+                if writable || mode.as_deref() != Some("r") {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
     pub async fn write(
         &self,
         key: impl AsRef<str>,
@@ -422,4 +459,24 @@ impl BlockDeviceRef {
             minor,
         }
     }
+
+    pub fn from_path(path: impl AsRef<str>) -> Result<Self> {
+        let metadata = std::fs::metadata(path.as_ref())?;
+        if !metadata.file_type().is_block_device() {
+            return Err(Error::NotBlockDevice(path.as_ref().to_string()));
+        }
+        let rdev = metadata.rdev();
+        Ok(Self::new(path, major(rdev) as u32, minor(rdev) as u32))
+    }
+
+    pub fn physical_device(&self) -> String {
+        format!("{:02x}:{:02x}", self.major, self.minor)
+    }
+
+    pub fn parse_physical_device(value: &str) -> Option<(u32, u32)> {
+        let (major, minor) = value.trim().split_once(':')?;
+        let major = u32::from_str_radix(major, 16).ok()?;
+        let minor = u32::from_str_radix(minor, 16).ok()?;
+        Some((major, minor))
+    }
 }
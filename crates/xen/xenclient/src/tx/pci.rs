@@ -4,7 +4,10 @@ use crate::{
     pci::{PciBdf, XenPciBackend},
 };
 use indexmap::IndexMap;
-use xencall::{sys::DOMCTL_DEV_RDM_RELAXED, XenCall};
+use xencall::{
+    sys::{DOMCTL_DEV_RDM_RELAXED, XEN_SYSCTL_PHYSCAP_DIRECTIO},
+    XenCall,
+};
 use xenplatform::sys::XEN_PAGE_SHIFT;
 
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
@@ -31,6 +34,7 @@ pub struct PciDeviceConfig {
     permissive: bool,
     msi_translate: bool,
     power_management: bool,
+    make_assignable: bool,
 }
 
 pub struct PciRootDeviceConfig {
@@ -46,6 +50,7 @@ impl PciDeviceConfig {
             permissive: false,
             msi_translate: false,
             power_management: false,
+            make_assignable: false,
         }
     }
 
@@ -69,6 +74,11 @@ impl PciDeviceConfig {
         self
     }
 
+    pub fn make_assignable(&mut self, make_assignable: bool) -> &mut Self {
+        self.make_assignable = make_assignable;
+        self
+    }
+
     pub fn done(self) -> Self {
         self
     }
@@ -99,12 +109,35 @@ impl PciRootDeviceConfig {
     }
 
     pub async fn prepare(&self, domid: u32, call: &XenCall) -> Result<()> {
+        if !self.devices.is_empty() {
+            let physinfo = call.phys_info().await?;
+            if (physinfo.capabilities & XEN_SYSCTL_PHYSCAP_DIRECTIO) == 0 {
+                return Err(Error::IommuUnavailable);
+            }
+        }
+
+        let backend = XenPciBackend::new();
         for device in &self.devices {
-            let backend = XenPciBackend::new();
-            if !backend.is_assigned(&device.bdf).await? {
+            let bdf = device.bdf.normalize();
+            let assignable = if device.make_assignable {
+                backend.is_loaded().await? && backend.is_present(&bdf).await?
+            } else {
+                backend.is_assigned(&bdf).await?
+            };
+            if !assignable {
                 return Err(Error::PciDeviceNotAssignable(device.bdf));
             }
-            let resources = backend.read_resources(&device.bdf).await?;
+        }
+
+        for device in &self.devices {
+            let bdf = device.bdf.normalize();
+            if device.make_assignable {
+                backend.make_assignable(&bdf).await?;
+                if !backend.is_assigned(&bdf).await? {
+                    return Err(Error::PciDeviceNotAssignable(device.bdf));
+                }
+            }
+            let resources = backend.read_resources(&bdf).await?;
             for resource in resources {
                 if resource.is_bar_io() {
                     call.ioport_permission(
@@ -125,16 +158,16 @@ impl PciRootDeviceConfig {
                 }
             }
 
-            if let Some(irq) = backend.read_irq(&device.bdf).await? {
+            if let Some(irq) = backend.read_irq(&bdf).await? {
                 let irq = call.map_pirq(domid, irq as isize, None).await?;
                 call.irq_permission(domid, irq, true).await?;
             }
 
-            backend.reset(&device.bdf).await?;
+            backend.reset(&bdf).await?;
 
             call.assign_device(
                 domid,
-                device.bdf.encode(),
+                bdf.encode(),
                 if device.rdm_reserve_policy == PciRdmReservePolicy::Relaxed {
                     DOMCTL_DEV_RDM_RELAXED
                 } else {
@@ -144,7 +177,7 @@ impl PciRootDeviceConfig {
             .await?;
 
             if device.permissive {
-                backend.enable_permissive(&device.bdf).await?;
+                backend.enable_permissive(&bdf).await?;
             }
         }
         Ok(())
@@ -177,9 +210,10 @@ impl DeviceConfig for PciRootDeviceConfig {
                 .map(|(key, value)| format!("{}={}", key, value))
                 .collect::<Vec<_>>()
                 .join(",");
+            let bdf = pci.bdf.normalize();
             device
-                .add_backend_item(format!("key-{}", index), pci.bdf.to_string())
-                .add_backend_item(format!("dev-{}", index), pci.bdf.to_string())
+                .add_backend_item(format!("key-{}", index), bdf.to_string())
+                .add_backend_item(format!("dev-{}", index), bdf.to_string())
                 .add_backend_item(format!("opts-{}", index), options);
 
             if let Some(vdefn) = pci.bdf.vdefn {
@@ -11,6 +11,7 @@ pub struct VbdDeviceConfig {
     writable: bool,
     discard: bool,
     trusted: bool,
+    check_sharing: bool,
     block_device: Option<BlockDeviceRef>,
 }
 
@@ -29,6 +30,7 @@ impl VbdDeviceConfig {
             writable: false,
             discard: false,
             trusted: true,
+            check_sharing: false,
             block_device: None,
         }
     }
@@ -63,6 +65,11 @@ impl VbdDeviceConfig {
         self
     }
 
+    pub fn check_sharing(&mut self, check_sharing: bool) -> &mut Self {
+        self.check_sharing = check_sharing;
+        self
+    }
+
     pub fn block_device(&mut self, block_device: BlockDeviceRef) -> &mut Self {
         self.block_device = Some(block_device);
         self
@@ -85,6 +92,13 @@ impl DeviceConfig for VbdDeviceConfig {
             .block_device
             .as_ref()
             .ok_or_else(|| Error::ParameterMissing("block device"))?;
+        if self.check_sharing
+            && tx
+                .is_block_device_in_use(block_device, self.writable)
+                .await?
+        {
+            return Err(Error::BlockDeviceInUse(block_device.path.clone()));
+        }
 
         let mut device = DeviceDescription::new("vbd", &self.backend_type);
         device
@@ -97,10 +111,7 @@ impl DeviceConfig for VbdDeviceConfig {
             .add_backend_item("specification", "xen")
             .add_backend_item("physical-device-path", &block_device.path)
             .add_backend_item("mode", if self.writable { "w" } else { "r" })
-            .add_backend_item(
-                "physical-device",
-                format!("{:02x}:{:02x}", block_device.major, block_device.minor),
-            )
+            .add_backend_item("physical-device", block_device.physical_device())
             .add_backend_item("dev", &vdev)
             .add_backend_item("state", 1);
 
@@ -7,6 +7,7 @@ pub struct VifDeviceConfig {
     mtu: Option<u32>,
     script: Option<String>,
     bridge: Option<String>,
+    ip: Option<String>,
     trusted: bool,
 }
 
@@ -24,6 +25,7 @@ impl VifDeviceConfig {
             mtu: None,
             script: None,
             bridge: None,
+            ip: None,
             trusted: true,
         }
     }
@@ -53,6 +55,11 @@ impl VifDeviceConfig {
         self
     }
 
+    pub fn ip(&mut self, ip: impl AsRef<str>) -> &mut Self {
+        self.ip = Some(ip.as_ref().to_string());
+        self
+    }
+
     pub fn trusted(&mut self, trusted: bool) -> &mut Self {
         self.trusted = trusted;
         self
@@ -90,6 +97,10 @@ impl DeviceConfig for VifDeviceConfig {
             device.add_backend_item("bridge", bridge);
         }
 
+        if let Some(ip) = self.ip.as_ref() {
+            device.add_backend_item("ip", ip);
+        }
+
         if let Some(script) = self.script.as_ref() {
             device
                 .add_backend_item("script", script)
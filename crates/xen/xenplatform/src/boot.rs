@@ -187,10 +187,11 @@ pub trait BootSetupPlatform {
     ) -> Result<()> {
         self.initialize_early(domain).await?;
 
-        let mut initrd_segment = if !domain.image_info.unmapped_initrd && kernel.initrd.is_some() {
-            Some(domain.alloc_module(kernel.initrd.as_ref().unwrap()).await?)
-        } else {
-            None
+        let mut initrd_segment = match kernel.initrd.as_ref() {
+            Some(initrd) if !domain.image_info.unmapped_initrd => {
+                Some(domain.alloc_module(initrd).await?)
+            }
+            _ => None,
         };
 
         let mut kernel_segment = if self.needs_early_kernel() {
@@ -206,8 +207,10 @@ pub trait BootSetupPlatform {
             kernel_segment = Some(self.load_kernel_segment(image_loader, domain).await?);
         }
 
-        if domain.image_info.unmapped_initrd && kernel.initrd.is_some() {
-            initrd_segment = Some(domain.alloc_module(kernel.initrd.as_ref().unwrap()).await?);
+        if let Some(initrd) = kernel.initrd.as_ref() {
+            if domain.image_info.unmapped_initrd {
+                initrd_segment = Some(domain.alloc_module(initrd).await?);
+            }
         }
 
         domain.initrd_segment = initrd_segment;
@@ -1,8 +1,11 @@
 use std::sync::Arc;
 
 use crate::{
-    boot::BootDomain, elfloader::ElfImageLoader, error::Error, ImageLoader, RuntimePlatform,
-    RuntimePlatformType,
+    boot::BootDomain,
+    elfloader::ElfImageLoader,
+    error::Error,
+    sys::{MAX_GUEST_CMDLINE, XEN_PAGE_SIZE},
+    ImageLoader, RuntimePlatform, RuntimePlatformType,
 };
 use log::warn;
 use uuid::Uuid;
@@ -25,6 +28,27 @@ impl PlatformDomainManager {
         (resources.max_memory_mb * 1024) + XEN_EXTRA_MEMORY_KB
     }
 
+    async fn check_host_memory(&self, config: &PlatformDomainConfig) -> Result<()> {
+        let physinfo = self.call.phys_info().await?;
+        let required_kb = PlatformDomainManager::max_memory_kb(&config.resources);
+        let available_pages = physinfo
+            .free_pages
+            .saturating_sub(physinfo.outstanding_pages);
+        let available_kb = available_pages * (XEN_PAGE_SIZE / 1024);
+        if required_kb > available_kb {
+            return Err(Error::InsufficientHostMemory(required_kb, available_kb));
+        }
+        Ok(())
+    }
+
+    fn check_cmdline(config: &PlatformDomainConfig) -> Result<()> {
+        let length = config.kernel.cmdline.len();
+        if length >= MAX_GUEST_CMDLINE {
+            return Err(Error::CmdlineTooLong(length, MAX_GUEST_CMDLINE - 1));
+        }
+        Ok(())
+    }
+
     async fn create_base_domain(
         &self,
         config: &PlatformDomainConfig,
@@ -83,6 +107,8 @@ impl PlatformDomainManager {
     }
 
     pub async fn create(&self, config: PlatformDomainConfig) -> Result<PlatformDomainInfo> {
+        PlatformDomainManager::check_cmdline(&config)?;
+        self.check_host_memory(&config).await?;
         let platform = config.platform.create();
         let domid = self.create_base_domain(&config, &platform).await?;
         let domain = match self.create_internal(domid, &config, platform).await {
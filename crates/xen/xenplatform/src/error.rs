@@ -26,6 +26,10 @@ pub enum Error {
     MemorySetupFailed(&'static str),
     #[error("populate physmap failed: wanted={0}, received={1}, input_extents={2}")]
     PopulatePhysmapFailed(usize, usize, usize),
+    #[error("insufficient host memory: required={0}KiB, available={1}KiB")]
+    InsufficientHostMemory(u64, u64),
+    #[error("kernel command line is too long: length={0}, max={1}")]
+    CmdlineTooLong(usize, usize),
     #[error("unknown elf compression method")]
     ElfCompressionUnknown,
     #[error("elf image format invalid: {0}")]
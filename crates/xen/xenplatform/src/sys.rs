@@ -116,6 +116,7 @@ pub const XEN_ELFNOTE_TYPES: &[ElfNoteXenType] = &[
 pub const XEN_PAGE_SHIFT: u64 = 12;
 pub const XEN_PAGE_SIZE: u64 = 1 << XEN_PAGE_SHIFT;
 pub const XEN_PAGE_MASK: u64 = !(XEN_PAGE_SIZE - 1);
+pub const MAX_GUEST_CMDLINE: usize = 1024;
 pub const SUPERPAGE_BATCH_SIZE: u64 = 512;
 pub const SUPERPAGE_2MB_SHIFT: u64 = 9;
 pub const SUPERPAGE_2MB_NR_PFNS: u64 = 1u64 << SUPERPAGE_2MB_SHIFT;
@@ -61,7 +61,7 @@ pub struct StartInfoConsole {
     pub evtchn: u32,
 }
 
-pub const MAX_GUEST_CMDLINE: usize = 1024;
+pub use crate::sys::MAX_GUEST_CMDLINE;
 
 #[repr(C)]
 #[derive(Debug)]
@@ -557,7 +557,7 @@ impl BootSetupPlatform for X86PvPlatform {
                     let p_e = (std::cmp::min(to, lvl.to) - from)
                         >> (X86_PAGE_SHIFT + l as u64 * X86_PGTABLE_LEVEL_SHIFT);
                     let rhs = X86_PAGE_SHIFT as usize + l * X86_PGTABLE_LEVEL_SHIFT as usize;
-                    let mut pfn = ((std::cmp::max(from, lvl.from) - lvl.from) >> rhs) + lvl.pfn;
+                    let pfn = ((std::cmp::max(from, lvl.from) - lvl.from) >> rhs) + lvl.pfn;
 
                     debug!(
                         "setup_page_tables lvl={} map_1={} map_2={} pfn={:#x} p_s={:#x} p_e={:#x}",
@@ -565,12 +565,11 @@ impl BootSetupPlatform for X86PvPlatform {
                     );
 
                     let pg = unsafe { slice::from_raw_parts_mut(pg_ptr, (p_e + 1) as usize) };
-                    for p in p_s..p_e + 1 {
+                    for (pfn, p) in (pfn..).zip(p_s..p_e + 1) {
                         let prot = self.get_pg_prot(l, pfn);
                         let pfn_paddr = domain.phys.p2m[pfn as usize] << X86_PAGE_SHIFT;
                         let value = pfn_paddr | prot;
                         pg[p as usize] = value;
-                        pfn += 1;
                     }
                 }
             }
@@ -609,6 +608,8 @@ impl BootSetupPlatform for X86PvPlatform {
             .as_ref()
             .ok_or(Error::MemorySetupFailed("start_info_segment missing"))?;
 
+        debug_assert!(domain.cmdline.len() < MAX_GUEST_CMDLINE);
+
         let ptr = domain.phys.pfn_to_ptr(start_info_segment.pfn, 1).await?;
         let byte_slice =
             unsafe { slice::from_raw_parts_mut(ptr as *mut u8, X86_PAGE_SIZE as usize) };
@@ -648,7 +649,7 @@ impl BootSetupPlatform for X86PvPlatform {
                 (*info).mod_start = initrd_segment.vstart;
                 (*info).mod_len = initrd_segment.size;
             }
-            for (i, c) in domain.cmdline.chars().enumerate() {
+            for (i, c) in domain.cmdline.bytes().enumerate() {
                 (*info).cmdline[i] = c as c_char;
             }
             (*info).cmdline[MAX_GUEST_CMDLINE - 1] = 0;
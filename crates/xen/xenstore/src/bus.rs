@@ -155,7 +155,8 @@ impl XsdSocket {
             header,
             payload: payload.to_vec(),
         };
-        if let Err(error) = self.tx_sender.try_send(message) {
+        if let Err(error) = self.tx_sender.send(message).await {
+            self.replies.lock().await.remove(&req);
             return Err(error.into());
         }
         let reply = receiver.await?;
@@ -245,16 +246,18 @@ impl XsdSocketProcessor {
                 x = self.rx_receiver.recv() => match x {
                     Some(message) => {
                         if message.header.typ == XSD_WATCH_EVENT && message.header.req == 0 && message.header.tx == 0 {
-                            let strings = message.parse_string_vec()?;
+                            let Ok(strings) = message.parse_string_vec() else {
+                                continue;
+                            };
                             let Some(path) = strings.first() else {
-                                return Ok(());
+                                continue;
                             };
                             let Some(token) = strings.get(1) else {
-                                return Ok(());
+                                continue;
                             };
 
                             let Ok(id) = token.parse::<u32>() else {
-                                return Ok(());
+                                continue;
                             };
 
                             if let Some(state) = self.watches.lock().await.get(&id) {